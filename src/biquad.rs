@@ -0,0 +1,124 @@
+use crate::signal::Signal;
+use crate::signals::SAMPLE_RATE;
+use fon::chan::Ch32;
+use fon::chan::Channel;
+
+const TAU: f32 = 6.283_185_5;
+
+#[derive(Clone, Copy)]
+enum Kind {
+    LowPass,
+    HighPass,
+    BandPass,
+}
+
+#[derive(Clone, Copy)]
+struct Coeffs {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+/// RBJ cookbook coefficients for the given kind, cutoff and Q.
+fn coeffs(kind: Kind, cutoff: f32, q: f32) -> Coeffs {
+    let w0 = TAU * cutoff / SAMPLE_RATE;
+    let cos_w0 = w0.cos();
+    let alpha = w0.sin() / (2.0 * q);
+
+    let (b0, b1, b2, a0, a1, a2) = match kind {
+        Kind::LowPass => {
+            let b1 = 1.0 - cos_w0;
+            (b1 / 2.0, b1, b1 / 2.0, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha)
+        }
+        Kind::HighPass => {
+            let b1 = -(1.0 + cos_w0);
+            (-b1 / 2.0, b1, -b1 / 2.0, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha)
+        }
+        Kind::BandPass => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha),
+    };
+
+    Coeffs {
+        b0: b0 / a0,
+        b1: b1 / a0,
+        b2: b2 / a0,
+        a1: a1 / a0,
+        a2: a2 / a0,
+    }
+}
+
+/// An RBJ transposed-direct-form-II biquad filter. The cutoff can be
+/// driven by another signal (e.g. an `Adsr` or `Every`) so filter sweeps
+/// can be recomputed per sample as the cutoff input changes.
+pub struct Biquad {
+    kind: Kind,
+    input: Box<dyn Signal>,
+    cutoff: Box<dyn Signal>,
+    q: f32,
+    last_cutoff: f32,
+    coeffs: Coeffs,
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    fn new(kind: Kind, input: Box<dyn Signal>, cutoff: Box<dyn Signal>, q: f32) -> Self {
+        Self {
+            kind,
+            input,
+            cutoff,
+            q,
+            last_cutoff: f32::NAN,
+            coeffs: Coeffs {
+                b0: 0.0,
+                b1: 0.0,
+                b2: 0.0,
+                a1: 0.0,
+                a2: 0.0,
+            },
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    pub fn low_pass(input: Box<dyn Signal>, cutoff: Box<dyn Signal>, q: f32) -> Self {
+        Self::new(Kind::LowPass, input, cutoff, q)
+    }
+
+    pub fn high_pass(input: Box<dyn Signal>, cutoff: Box<dyn Signal>, q: f32) -> Self {
+        Self::new(Kind::HighPass, input, cutoff, q)
+    }
+
+    pub fn band_pass(input: Box<dyn Signal>, cutoff: Box<dyn Signal>, q: f32) -> Self {
+        Self::new(Kind::BandPass, input, cutoff, q)
+    }
+}
+
+impl Signal for Biquad {
+    fn sample(&mut self, t: f32) -> Ch32 {
+        let cutoff = self.cutoff.sample(t).to_f32();
+        if cutoff != self.last_cutoff {
+            self.coeffs = coeffs(self.kind, cutoff, self.q);
+            self.last_cutoff = cutoff;
+        }
+
+        let x = self.input.sample(t).to_f32();
+        let Coeffs { b0, b1, b2, a1, a2 } = self.coeffs;
+
+        let out = b0 * x + self.z1;
+        self.z1 = b1 * x - a1 * out + self.z2;
+        self.z2 = b2 * x - a2 * out;
+
+        Ch32::from(out)
+    }
+
+    fn clone_box(&self) -> Box<dyn Signal> {
+        Box::new(Biquad::new(
+            self.kind,
+            self.input.clone_box(),
+            self.cutoff.clone_box(),
+            self.q,
+        ))
+    }
+}
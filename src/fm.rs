@@ -0,0 +1,151 @@
+use crate::signal::Signal;
+use crate::signals::SAMPLE_PERIOD;
+use fon::chan::Ch32;
+use fon::chan::Channel;
+
+const TAU: f32 = 6.283_185_5;
+
+/// One FM operand: a phase-accumulator sine oscillator running at
+/// `base_freq * multiplier`, phase-modulated (not frequency-modulated,
+/// unlike `Sine`) by whatever feeds into it.
+#[derive(Clone, Copy)]
+pub struct Operator {
+    phase: f32,
+    multiplier: f32,
+    level: f32,
+}
+
+impl Operator {
+    pub fn new(multiplier: f32, level: f32) -> Self {
+        Self {
+            phase: 0.0,
+            multiplier,
+            level,
+        }
+    }
+
+    /// Advances the phase by one sample and returns the operator's output,
+    /// `sin(phase + modulation) * level`.
+    fn step(&mut self, base_freq: f32, modulation: f32) -> f32 {
+        let out = (self.phase + modulation).sin() * self.level;
+        self.phase = (self.phase + TAU * SAMPLE_PERIOD * base_freq * self.multiplier) % TAU;
+        out
+    }
+}
+
+/// One of the eight classic 4-operator FM routings (as on the YM2612),
+/// from fully serial to fully parallel. Operators are numbered op1..op4 as
+/// in the hardware naming, where op1 is always a carrier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    /// op4 -> op3 -> op2 -> op1, single carrier.
+    A0,
+    /// op4 -> op3 -> op1, op2 -> op1.
+    A1,
+    /// op4 -> op2 -> op1, op3 -> op1.
+    A2,
+    /// op4 -> op1, op3 -> op1, op2 -> op1.
+    A3,
+    /// op4 -> op3 -> op2, carriers op2 and op1 summed.
+    A4,
+    /// op4 modulates op3, op2, and op1 in parallel; op4 is silent, the
+    /// other three are carriers.
+    A5,
+    /// op4 -> op3, carriers op3, op2, op1 summed.
+    A6,
+    /// op1, op2, op3, op4 all in parallel, no modulation at all.
+    A7,
+}
+
+/// A four-operator FM voice: owns the operators and wires them together
+/// per `algorithm`, summing whichever operators are carriers for that
+/// routing.
+pub struct FmVoice {
+    freq: Box<dyn Signal>,
+    algorithm: Algorithm,
+    operators: [Operator; 4],
+}
+
+impl FmVoice {
+    pub fn new(freq: Box<dyn Signal>, algorithm: Algorithm, operators: [Operator; 4]) -> Self {
+        Self {
+            freq,
+            algorithm,
+            operators,
+        }
+    }
+
+    fn eval(&mut self, base_freq: f32) -> f32 {
+        let [op1, op2, op3, op4] = &mut self.operators;
+
+        match self.algorithm {
+            Algorithm::A0 => {
+                let m4 = op4.step(base_freq, 0.0);
+                let m3 = op3.step(base_freq, m4);
+                let m2 = op2.step(base_freq, m3);
+                op1.step(base_freq, m2)
+            }
+            Algorithm::A1 => {
+                let m4 = op4.step(base_freq, 0.0);
+                let m3 = op3.step(base_freq, m4);
+                let m2 = op2.step(base_freq, 0.0);
+                op1.step(base_freq, m3 + m2)
+            }
+            Algorithm::A2 => {
+                let m4 = op4.step(base_freq, 0.0);
+                let m2 = op2.step(base_freq, m4);
+                let m3 = op3.step(base_freq, 0.0);
+                op1.step(base_freq, m2 + m3)
+            }
+            Algorithm::A3 => {
+                let m4 = op4.step(base_freq, 0.0);
+                let m3 = op3.step(base_freq, 0.0);
+                let m2 = op2.step(base_freq, 0.0);
+                op1.step(base_freq, m4 + m3 + m2)
+            }
+            Algorithm::A4 => {
+                let m4 = op4.step(base_freq, 0.0);
+                let m3 = op3.step(base_freq, m4);
+                let c2 = op2.step(base_freq, m3);
+                let c1 = op1.step(base_freq, 0.0);
+                c1 + c2
+            }
+            Algorithm::A5 => {
+                let m4 = op4.step(base_freq, 0.0);
+                let c3 = op3.step(base_freq, m4);
+                let c2 = op2.step(base_freq, m4);
+                let c1 = op1.step(base_freq, m4);
+                c1 + c2 + c3
+            }
+            Algorithm::A6 => {
+                let m4 = op4.step(base_freq, 0.0);
+                let c3 = op3.step(base_freq, m4);
+                let c2 = op2.step(base_freq, 0.0);
+                let c1 = op1.step(base_freq, 0.0);
+                c1 + c2 + c3
+            }
+            Algorithm::A7 => {
+                let c4 = op4.step(base_freq, 0.0);
+                let c3 = op3.step(base_freq, 0.0);
+                let c2 = op2.step(base_freq, 0.0);
+                let c1 = op1.step(base_freq, 0.0);
+                c1 + c2 + c3 + c4
+            }
+        }
+    }
+}
+
+impl Signal for FmVoice {
+    fn sample(&mut self, t: f32) -> Ch32 {
+        let base_freq = self.freq.sample(t).to_f32();
+        Ch32::from(self.eval(base_freq))
+    }
+
+    fn clone_box(&self) -> Box<dyn Signal> {
+        Box::new(FmVoice::new(
+            self.freq.clone_box(),
+            self.algorithm,
+            self.operators,
+        ))
+    }
+}
@@ -0,0 +1,108 @@
+use crate::signal::Signal;
+use fon::chan::Ch32;
+use fon::chan::Channel;
+use std::collections::VecDeque;
+
+/// A sliding-maximum window over the last `capacity` pushed values,
+/// backed by a binary tree of ring-buffer leaves. Each internal node
+/// caches the max of its two children, so pushing a new value and reading
+/// the window max are both O(log capacity) / O(1) respectively.
+struct MaxTree {
+    capacity: usize,
+    tree: Vec<f32>,
+    write: usize,
+}
+
+impl MaxTree {
+    fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1).next_power_of_two();
+        Self {
+            capacity,
+            tree: vec![0.0; capacity * 2],
+            write: 0,
+        }
+    }
+
+    /// Overwrites the oldest leaf with `value` and propagates the new max
+    /// up to the root.
+    fn push(&mut self, value: f32) {
+        let mut i = self.capacity + self.write;
+        self.tree[i] = value;
+        while i > 1 {
+            i /= 2;
+            self.tree[i] = self.tree[2 * i].max(self.tree[2 * i + 1]);
+        }
+        self.write = (self.write + 1) % self.capacity;
+    }
+
+    /// The maximum of the last `capacity` pushed values.
+    fn max(&self) -> f32 {
+        self.tree[1]
+    }
+}
+
+/// A look-ahead limiter/compressor: detects the peak amplitude over a
+/// look-ahead window and smoothly reduces gain before that peak actually
+/// reaches the output, so summing many harmonics/voices doesn't clip.
+pub struct Limiter {
+    input: Box<dyn Signal>,
+    threshold: f32,
+    lookahead: usize,
+    attack: f32,
+    release: f32,
+    peaks: MaxTree,
+    delay: VecDeque<f32>,
+    gain: f32,
+}
+
+impl Limiter {
+    /// `attack` and `release` are one-pole smoothing coefficients in
+    /// `[0, 1]` applied to the gain each sample.
+    pub fn new(input: Box<dyn Signal>, threshold: f32, lookahead: usize, attack: f32, release: f32) -> Self {
+        let lookahead = lookahead.max(1);
+        let mut delay = VecDeque::with_capacity(lookahead + 1);
+        delay.extend(std::iter::repeat(0.0).take(lookahead));
+
+        Self {
+            input,
+            threshold,
+            lookahead,
+            attack,
+            release,
+            peaks: MaxTree::new(lookahead),
+            delay,
+            gain: 1.0,
+        }
+    }
+}
+
+impl Signal for Limiter {
+    fn sample(&mut self, t: f32) -> Ch32 {
+        let x = self.input.sample(t).to_f32();
+
+        self.peaks.push(x.abs());
+        self.delay.push_back(x);
+        let delayed = self.delay.pop_front().unwrap_or(0.0);
+
+        let peak = self.peaks.max().max(1e-9);
+        let target_gain = (self.threshold / peak).min(1.0);
+        let coeff = if target_gain < self.gain {
+            self.attack
+        } else {
+            self.release
+        };
+        self.gain += (target_gain - self.gain) * coeff;
+
+        Ch32::from(delayed * self.gain)
+    }
+
+    fn clone_box(&self) -> Box<dyn Signal> {
+        Box::new(Limiter::new(
+            self.input.clone_box(),
+            self.threshold,
+            self.lookahead,
+            self.attack,
+            self.release,
+        ))
+    }
+}
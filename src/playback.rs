@@ -0,0 +1,84 @@
+use crate::signal::Signal;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{SampleFormat, StreamConfig};
+use fon::chan::Channel;
+use std::sync::Mutex;
+
+/// Streams `signal` live to the default output device, writing the same
+/// value to both channels until stereo signals exist. Blocks the calling
+/// thread for the given duration so the stream stays alive long enough to
+/// be heard; the underlying `cpal::Stream` is stopped when it's dropped.
+pub fn play(signal: Box<dyn Signal>, duration_secs: f32) {
+    let host = cpal::default_host();
+    let device = host
+        .default_output_device()
+        .expect("no default output device");
+    let config = device
+        .default_output_config()
+        .expect("no default output config");
+
+    let sample_format = config.sample_format();
+    let config: StreamConfig = config.into();
+    let channels = config.channels as usize;
+    let sample_period = 1.0 / config.sample_rate.0 as f32;
+
+    let state = Mutex::new((signal, 0u64));
+    let err_fn = |err| eprintln!("stream error: {err}");
+
+    let stream = match sample_format {
+        SampleFormat::F32 => device.build_output_stream(
+            &config,
+            move |data: &mut [f32], _| write_frames(data, channels, sample_period, &state, |v| v),
+            err_fn,
+            None,
+        ),
+        SampleFormat::I16 => device.build_output_stream(
+            &config,
+            move |data: &mut [i16], _| {
+                write_frames(data, channels, sample_period, &state, |v| {
+                    (v * i16::MAX as f32) as i16
+                })
+            },
+            err_fn,
+            None,
+        ),
+        SampleFormat::U16 => device.build_output_stream(
+            &config,
+            move |data: &mut [u16], _| {
+                write_frames(data, channels, sample_period, &state, |v| {
+                    ((v * 0.5 + 0.5) * u16::MAX as f32) as u16
+                })
+            },
+            err_fn,
+            None,
+        ),
+        format => panic!("unsupported sample format: {format}"),
+    }
+    .expect("failed to build output stream");
+
+    stream.play().expect("failed to start stream");
+    std::thread::sleep(std::time::Duration::from_secs_f32(duration_secs));
+}
+
+/// Advances the shared sample counter by one frame per output frame and
+/// writes the converted value to every channel of that frame. `sample_period`
+/// is `1 / config.sample_rate` for the stream actually negotiated with the
+/// device, which is not always the engine's own 44.1 kHz `SAMPLE_RATE`.
+fn write_frames<S: Copy>(
+    data: &mut [S],
+    channels: usize,
+    sample_period: f32,
+    state: &Mutex<(Box<dyn Signal>, u64)>,
+    to_sample: impl Fn(f32) -> S,
+) {
+    let mut state = state.lock().unwrap();
+    for frame in data.chunks_mut(channels) {
+        let (signal, n) = &mut *state;
+        let t = *n as f32 * sample_period;
+        *n += 1;
+        let value = to_sample(signal.sample(t).to_f32());
+        for out in frame {
+            *out = value;
+        }
+    }
+}
@@ -1,6 +1,10 @@
 use fon::chan::Ch32;
 
-pub trait Signal {
+/// A mono audio source sampled one point in time at a time.
+///
+/// `Signal` is required to be `Send` so any signal graph can be handed off
+/// to a real-time audio callback thread (see `playback::play`).
+pub trait Signal: Send {
     fn sample(&mut self, t: f32) -> Ch32;
 
     fn clone_box(&self) -> Box<dyn Signal>;
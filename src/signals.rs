@@ -60,6 +60,147 @@ impl Signal for Sine {
     }
 }
 
+/// PolyBLEP (polynomial band-limited step) correction applied at a phase
+/// discontinuity, where `t` is the normalized phase (`[0, 1)`) and `dt`
+/// is the phase increment per sample. Subtracting this near a wrapped
+/// edge removes most of the aliasing a naive saw/square would have.
+fn poly_blep(mut t: f32, dt: f32) -> f32 {
+    if t < dt {
+        t /= dt;
+        t + t - t * t - 1.0
+    } else if t > 1.0 - dt {
+        t = (t - 1.0) / dt;
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+pub struct Saw {
+    freq: Box<dyn Signal>,
+    phase: f32,
+}
+
+impl Saw {
+    pub fn new(freq: Box<dyn Signal>) -> Self {
+        Self { freq, phase: 0.0 }
+    }
+}
+
+impl Signal for Saw {
+    fn sample(&mut self, t: f32) -> Ch32 {
+        let dt = self.freq.sample(t).to_f32() * SAMPLE_PERIOD;
+        let out = 2.0 * self.phase - 1.0 - poly_blep(self.phase, dt);
+        self.phase = (self.phase + dt) % 1.0;
+        Ch32::from(out)
+    }
+
+    fn clone_box(&self) -> Box<dyn Signal> {
+        Box::new(Saw::new(self.freq.clone_box()))
+    }
+}
+
+pub struct Square {
+    freq: Box<dyn Signal>,
+    pulse_width: f32,
+    phase: f32,
+}
+
+impl Square {
+    pub fn new(freq: Box<dyn Signal>, pulse_width: f32) -> Self {
+        Self {
+            freq,
+            pulse_width,
+            phase: 0.0,
+        }
+    }
+}
+
+impl Signal for Square {
+    fn sample(&mut self, t: f32) -> Ch32 {
+        let dt = self.freq.sample(t).to_f32() * SAMPLE_PERIOD;
+
+        let mut out = if self.phase < self.pulse_width { 1.0 } else { -1.0 };
+        out += poly_blep(self.phase, dt);
+        out -= poly_blep((self.phase + 1.0 - self.pulse_width) % 1.0, dt);
+
+        self.phase = (self.phase + dt) % 1.0;
+        Ch32::from(out)
+    }
+
+    fn clone_box(&self) -> Box<dyn Signal> {
+        Box::new(Square::new(self.freq.clone_box(), self.pulse_width))
+    }
+}
+
+pub struct Triangle {
+    freq: Box<dyn Signal>,
+    phase: f32,
+    integrator: f32,
+}
+
+impl Triangle {
+    pub fn new(freq: Box<dyn Signal>) -> Self {
+        Self {
+            freq,
+            phase: 0.0,
+            integrator: 0.0,
+        }
+    }
+}
+
+impl Signal for Triangle {
+    fn sample(&mut self, t: f32) -> Ch32 {
+        let dt = self.freq.sample(t).to_f32() * SAMPLE_PERIOD;
+
+        let mut square = if self.phase < 0.5 { 1.0 } else { -1.0 };
+        square += poly_blep(self.phase, dt);
+        square -= poly_blep((self.phase + 0.5) % 1.0, dt);
+
+        // Leaky-integrating the band-limited square turns it into a
+        // band-limited triangle.
+        self.integrator = dt * square + (1.0 - dt) * self.integrator;
+        self.phase = (self.phase + dt) % 1.0;
+
+        Ch32::from(self.integrator * 4.0)
+    }
+
+    fn clone_box(&self) -> Box<dyn Signal> {
+        Box::new(Triangle::new(self.freq.clone_box()))
+    }
+}
+
+/// A fast xorshift32 PRNG noise source.
+pub struct White {
+    seed: u32,
+    state: u32,
+}
+
+impl White {
+    pub fn new(seed: u32) -> Self {
+        let seed = seed.max(1);
+        Self { seed, state: seed }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state
+    }
+}
+
+impl Signal for White {
+    fn sample(&mut self, _t: f32) -> Ch32 {
+        let bits = self.next_u32();
+        Ch32::from((bits as f32 / u32::MAX as f32) * 2.0 - 1.0)
+    }
+
+    fn clone_box(&self) -> Box<dyn Signal> {
+        Box::new(White::new(self.seed))
+    }
+}
+
 pub struct Gain {
     signal: Box<dyn Signal>,
     gain: f32,
@@ -143,6 +284,16 @@ impl Adsr {
             gate_last: false,
         }
     }
+
+    /// Overrides the default attack/decay/sustain/release times (seconds,
+    /// except `sustain_level` which is a level).
+    pub fn with_envelope(mut self, attack: f32, decay: f32, sustain_level: f32, release: f32) -> Self {
+        self.attack = attack;
+        self.decay = decay;
+        self.sustain_level = sustain_level;
+        self.release = release;
+        self
+    }
 }
 
 impl Signal for Adsr {
@@ -202,26 +353,42 @@ impl Signal for Adsr {
     }
 
     fn clone_box(&self) -> Box<dyn Signal> {
-        Box::new(Adsr::new(self.gate.clone_box(), self.input.clone_box()))
+        Box::new(
+            Adsr::new(self.gate.clone_box(), self.input.clone_box()).with_envelope(
+                self.attack,
+                self.decay,
+                self.sustain_level,
+                self.release,
+            ),
+        )
     }
 }
 
 pub struct Sample {
     gate: Box<dyn Signal>,
+    speed: Box<dyn Signal>,
     samples: Vec<Ch32>,
-    index: usize,
+    src_rate: f32,
+    pos: f32,
 }
 
 impl Sample {
-    pub fn new(file: &str, gate: Box<dyn Signal>) -> Self {
+    /// `speed` is a playback-speed/pitch multiplier (1.0 is the file's
+    /// native pitch); it can be driven by another signal to track note
+    /// frequency. Playback is resampled on the fly from the WAV's own
+    /// sample rate to the engine's `SAMPLE_RATE`.
+    pub fn new(file: &str, gate: Box<dyn Signal>, speed: Box<dyn Signal>) -> Self {
         let mut reader = hound::WavReader::open(file).unwrap();
+        let src_rate = reader.spec().sample_rate as f32;
         Sample {
             gate,
+            speed,
             samples: reader
                 .samples::<i16>()
                 .map(|x| Ch32::from(Ch16::new(x.unwrap())))
                 .collect::<Vec<_>>(),
-            index: 0,
+            src_rate,
+            pos: 0.0,
         }
     }
 }
@@ -230,22 +397,35 @@ impl Signal for Sample {
     fn sample(&mut self, t: f32) -> Ch32 {
         let gate = self.gate.sample(t).to_f32();
         if gate <= 0.0 {
-            self.index = 0;
-            Ch32::from(0.0)
-        } else if self.index < self.samples.len() {
-            let sample = self.samples[self.index];
-            self.index += 1;
-            sample
-        } else {
-            Ch32::from(0.0)
+            self.pos = 0.0;
+            return Ch32::from(0.0);
         }
+
+        let index = self.pos as usize;
+        let frac = self.pos - index as f32;
+        let out = match (self.samples.get(index), self.samples.get(index + 1)) {
+            (Some(&a), Some(&b)) => {
+                let a = a.to_f32();
+                let b = b.to_f32();
+                a + (b - a) * frac
+            }
+            (Some(&a), None) => a.to_f32(),
+            _ => 0.0,
+        };
+
+        let speed = self.speed.sample(t).to_f32();
+        self.pos += self.src_rate / SAMPLE_RATE * speed;
+
+        Ch32::from(out)
     }
 
     fn clone_box(&self) -> Box<dyn Signal> {
         Box::new(Sample {
             gate: self.gate.clone_box(),
+            speed: self.speed.clone_box(),
             samples: self.samples.clone(),
-            index: 0,
+            src_rate: self.src_rate,
+            pos: 0.0,
         })
     }
 }
@@ -276,6 +456,78 @@ impl Signal for Every {
     }
 }
 
+/// A slew-limiter: smooths a jumpy input signal (e.g. `StepSignal`
+/// switching frequencies) by moving an internal value toward the input's
+/// current target by at most one step per sample, where the step size
+/// comes from a configurable glide time in seconds. Useful for portamento
+/// between notes.
+pub struct Glide {
+    inner: Box<dyn Signal>,
+    glide_secs: f32,
+    actual: f32,
+    initialized: bool,
+    min: Option<f32>,
+    max: Option<f32>,
+}
+
+impl Glide {
+    pub fn new(inner: Box<dyn Signal>, glide_secs: f32) -> Self {
+        Self {
+            inner,
+            glide_secs,
+            actual: 0.0,
+            initialized: false,
+            min: None,
+            max: None,
+        }
+    }
+
+    /// Clamps the glided output to `[min, max]`.
+    pub fn with_clamp(mut self, min: f32, max: f32) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+}
+
+impl Signal for Glide {
+    fn sample(&mut self, t: f32) -> Ch32 {
+        let target = self.inner.sample(t).to_f32();
+
+        if !self.initialized {
+            self.actual = target;
+            self.initialized = true;
+        } else {
+            let target_delta = target - self.actual;
+            let step = target_delta / (self.glide_secs * SAMPLE_RATE).max(1.0);
+            self.actual += step;
+            if (target - self.actual).abs() <= step.abs() {
+                self.actual = target;
+            }
+        }
+
+        let mut out = self.actual;
+        if let Some(min) = self.min {
+            out = out.max(min);
+        }
+        if let Some(max) = self.max {
+            out = out.min(max);
+        }
+        Ch32::from(out)
+    }
+
+    fn clone_box(&self) -> Box<dyn Signal> {
+        Box::new(Glide {
+            inner: self.inner.clone_box(),
+            glide_secs: self.glide_secs,
+            actual: 0.0,
+            initialized: false,
+            min: self.min,
+            max: self.max,
+        })
+    }
+}
+
 pub struct StepSignal {
     steps: Vec<(Box<dyn Signal>, f32)>,
     total_time: f32,
@@ -0,0 +1,280 @@
+use crate::signal::Signal;
+use crate::signals::{Adsr, Const, Gain, Sum, SAMPLE_RATE};
+use fon::Audio;
+use fon::chan::Ch32;
+use fon::chan::Channel;
+
+/// A note scheduled on a `Track`: play `pitch` Hz starting at `start_beat`
+/// for `length_beats`. Notes on the same track may overlap.
+#[derive(Clone, Copy)]
+pub struct Note {
+    pub pitch: f32,
+    pub start_beat: f32,
+    pub length_beats: f32,
+}
+
+impl Note {
+    pub fn new(pitch: f32, start_beat: f32, length_beats: f32) -> Self {
+        Self {
+            pitch,
+            start_beat,
+            length_beats,
+        }
+    }
+}
+
+/// The ADSR shape applied to every voice triggered on a track.
+#[derive(Clone, Copy)]
+pub struct EnvelopeSpec {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain_level: f32,
+    pub release: f32,
+}
+
+/// High whenever `t` falls inside any of a voice-pool slot's assigned
+/// note windows, low otherwise (including the gaps between them), so the
+/// slot's shared `Adsr` retriggers once per note.
+struct UnionGate {
+    windows: Vec<(f32, f32)>,
+}
+
+impl Signal for UnionGate {
+    fn sample(&mut self, t: f32) -> Ch32 {
+        let hot = self
+            .windows
+            .iter()
+            .any(|&(start, end)| t >= start && t < end);
+        Ch32::new(if hot { 1.0 } else { 0.0 })
+    }
+
+    fn clone_box(&self) -> Box<dyn Signal> {
+        Box::new(UnionGate {
+            windows: self.windows.clone(),
+        })
+    }
+}
+
+/// The instrument side of a voice-pool slot: plays each assigned note's
+/// instrument only during that note's own `[start, end)` window (notes in
+/// a slot never overlap, so at most one is ever live), silent elsewhere.
+/// Unlike `StepSignal` this does not loop.
+struct VoiceSlot {
+    notes: Vec<(f32, f32, Box<dyn Signal>)>,
+}
+
+impl Signal for VoiceSlot {
+    fn sample(&mut self, t: f32) -> Ch32 {
+        for (start, end, instrument) in &mut self.notes {
+            if t >= *start && t < *end {
+                return instrument.sample(t - *start);
+            }
+        }
+        Ch32::from(0.0)
+    }
+
+    fn clone_box(&self) -> Box<dyn Signal> {
+        Box::new(VoiceSlot {
+            notes: self
+                .notes
+                .iter()
+                .map(|(start, end, instrument)| (*start, *end, instrument.clone_box()))
+                .collect(),
+        })
+    }
+}
+
+/// A feedback delay line mixed back in with the dry signal; used for a
+/// track's optional echo.
+struct Echo {
+    inner: Box<dyn Signal>,
+    buffer: Vec<f32>,
+    write: usize,
+    feedback: f32,
+    mix: f32,
+}
+
+impl Echo {
+    fn new(inner: Box<dyn Signal>, delay_secs: f32, feedback: f32, mix: f32) -> Self {
+        let len = ((delay_secs * SAMPLE_RATE) as usize).max(1);
+        Self {
+            inner,
+            buffer: vec![0.0; len],
+            write: 0,
+            feedback,
+            mix,
+        }
+    }
+}
+
+impl Signal for Echo {
+    fn sample(&mut self, t: f32) -> Ch32 {
+        let dry = self.inner.sample(t).to_f32();
+        let delayed = self.buffer[self.write];
+        self.buffer[self.write] = dry + delayed * self.feedback;
+        self.write = (self.write + 1) % self.buffer.len();
+        Ch32::from(dry + delayed * self.mix)
+    }
+
+    fn clone_box(&self) -> Box<dyn Signal> {
+        Box::new(Echo {
+            inner: self.inner.clone_box(),
+            buffer: vec![0.0; self.buffer.len()],
+            write: 0,
+            feedback: self.feedback,
+            mix: self.mix,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct EchoSpec {
+    delay_secs: f32,
+    feedback: f32,
+    mix: f32,
+}
+
+/// Builds a fresh instrument signal for a triggered note's pitch.
+pub type InstrumentFactory = Box<dyn Fn(f32) -> Box<dyn Signal> + Send>;
+
+/// A single instrument part: an instrument factory (invoked once per
+/// triggered note to build that voice's signal), an envelope shape, a
+/// list of (possibly overlapping) timed notes, and optional gain/pan/echo.
+pub struct Track {
+    instrument: InstrumentFactory,
+    envelope: EnvelopeSpec,
+    notes: Vec<Note>,
+    gain: f32,
+    pan: f32,
+    echo: Option<EchoSpec>,
+}
+
+impl Track {
+    pub fn new(instrument: InstrumentFactory, envelope: EnvelopeSpec, notes: Vec<Note>) -> Self {
+        Self {
+            instrument,
+            envelope,
+            notes,
+            gain: 1.0,
+            pan: 0.0,
+            echo: None,
+        }
+    }
+
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    pub fn with_pan(mut self, pan: f32) -> Self {
+        self.pan = pan;
+        self
+    }
+
+    pub fn with_echo(mut self, delay_secs: f32, feedback: f32, mix: f32) -> Self {
+        self.echo = Some(EchoSpec {
+            delay_secs,
+            feedback,
+            mix,
+        });
+        self
+    }
+
+    /// Builds the summed signal for this track, drawing from a bounded
+    /// pool of voices: each note is assigned to the first slot whose
+    /// previous note ended at least one sample before it starts (or a fresh
+    /// slot if none is free), so the slot's gate drops between its notes and
+    /// the number of concurrent `Adsr`+instrument nodes is the actual
+    /// maximum overlap rather than one per note.
+    fn render(&self, beat_secs: f32) -> Box<dyn Signal> {
+        let mut notes = self.notes.clone();
+        notes.sort_by(|a, b| a.start_beat.partial_cmp(&b.start_beat).unwrap());
+
+        let mut slots: Vec<Vec<(f32, f32, f32)>> = Vec::new();
+        let mut slot_ends: Vec<f32> = Vec::new();
+
+        for note in &notes {
+            let start = note.start_beat * beat_secs;
+            let end = (note.start_beat + note.length_beats) * beat_secs;
+
+            match slot_ends
+                .iter()
+                .position(|&slot_end| slot_end + 1.0 / SAMPLE_RATE <= start)
+            {
+                Some(i) => {
+                    slot_ends[i] = end;
+                    slots[i].push((start, end, note.pitch));
+                }
+                None => {
+                    slot_ends.push(end);
+                    slots.push(vec![(start, end, note.pitch)]);
+                }
+            }
+        }
+
+        let mut sum: Box<dyn Signal> = Box::new(Const::new(0.0));
+
+        for slot in slots {
+            let gate: Box<dyn Signal> = Box::new(UnionGate {
+                windows: slot.iter().map(|&(start, end, _)| (start, end)).collect(),
+            });
+            let instrument: Box<dyn Signal> = Box::new(VoiceSlot {
+                notes: slot
+                    .into_iter()
+                    .map(|(start, end, pitch)| (start, end, (self.instrument)(pitch)))
+                    .collect(),
+            });
+            let voice: Box<dyn Signal> = Box::new(Adsr::new(gate, instrument).with_envelope(
+                self.envelope.attack,
+                self.envelope.decay,
+                self.envelope.sustain_level,
+                self.envelope.release,
+            ));
+            sum = Box::new(Sum::new(sum, voice));
+        }
+
+        let sum: Box<dyn Signal> = Box::new(Gain::new(sum, self.gain));
+        match self.echo {
+            Some(echo) => Box::new(Echo::new(sum, echo.delay_secs, echo.feedback, echo.mix)),
+            None => sum,
+        }
+    }
+}
+
+/// A multi-track song rendered at a fixed tempo.
+pub struct Song {
+    bpm: u32,
+    tracks: Vec<Track>,
+}
+
+impl Song {
+    pub fn new(bpm: u32, tracks: Vec<Track>) -> Self {
+        Self { bpm, tracks }
+    }
+
+    /// Renders every track to its own signal and mixes them into a stereo
+    /// buffer of `duration_secs` length, panning each track independently.
+    pub fn render(&self, duration_secs: f32) -> Audio<Ch32, 2> {
+        let beat_secs = 60.0 / self.bpm as f32;
+        let mut track_signals: Vec<(Box<dyn Signal>, f32)> = self
+            .tracks
+            .iter()
+            .map(|track| (track.render(beat_secs), track.pan))
+            .collect();
+
+        let mut audio = Audio::<Ch32, 2>::with_silence(
+            SAMPLE_RATE as u32,
+            (SAMPLE_RATE * duration_secs) as usize,
+        );
+
+        for (i, frame) in audio.iter_mut().enumerate() {
+            let t = i as f32 / SAMPLE_RATE;
+            for (signal, pan) in &mut track_signals {
+                let sample = signal.sample(t);
+                *frame = frame.pan(sample, *pan);
+            }
+        }
+
+        audio
+    }
+}